@@ -0,0 +1,254 @@
+//! FastCDC content-defined chunking.
+//!
+//! Splits a file into variable-length chunks whose boundaries are determined
+//! by the file's own content rather than fixed offsets, so that after a game
+//! update only the chunks covering the changed regions differ — the rest of
+//! `Core.dll`/`level0` can be recognized as unchanged and skipped instead of
+//! re-hashing (or re-downloading) the whole file.
+
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+const fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+/// Fixed 256-entry "gear" table used to roll the content hash one byte at a
+/// time. The values just need to look random and never change between runs,
+/// so they're generated once at compile time from a fixed seed rather than
+/// hand-written.
+const fn gen_gear_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut i = 0;
+    let mut state = 0x2545_F491_4F6C_DD1D_u64;
+    while i < 256 {
+        state = splitmix64(state);
+        table[i] = state;
+        i += 1;
+    }
+    table
+}
+
+const GEAR: [u64; 256] = gen_gear_table();
+
+/// Bounds and target for chunk sizes. The defaults (2 KiB / 8 KiB / 64 KiB)
+/// keep chunk-size variance bounded even though content-defined chunking is
+/// inherently non-uniform.
+#[derive(Debug, Clone, Copy)]
+pub struct ChunkParams {
+    pub min_size: usize,
+    pub avg_size: usize,
+    pub max_size: usize,
+}
+
+impl Default for ChunkParams {
+    fn default() -> Self {
+        ChunkParams {
+            min_size: 2 * 1024,
+            avg_size: 8 * 1024,
+            max_size: 64 * 1024,
+        }
+    }
+}
+
+fn mask_with_ones(ones: u32) -> u64 {
+    if ones == 0 {
+        0
+    } else {
+        (1u64 << ones.min(63)) - 1
+    }
+}
+
+/// One content-defined chunk: its position in the file, its length, and its
+/// digest under both algorithms the rest of this program already computes.
+#[derive(Debug, Clone)]
+pub struct ChunkInfo {
+    pub offset: u64,
+    pub length: u64,
+    pub sha256: String,
+    pub blake3: String,
+}
+
+fn make_chunk(data: &[u8], offset: usize, length: usize) -> ChunkInfo {
+    let slice = &data[offset..offset + length];
+
+    let mut sha256_hasher = Sha256::new();
+    sha256_hasher.update(slice);
+    let sha256 = format!("{:x}", sha256_hasher.finalize());
+
+    let blake3 = blake3::hash(slice).to_hex().to_string();
+
+    ChunkInfo {
+        offset: offset as u64,
+        length: length as u64,
+        sha256,
+        blake3,
+    }
+}
+
+/// Splits `data` into content-defined chunks using normalized FastCDC.
+///
+/// A 64-bit rolling hash is updated one byte at a time as
+/// `hash = (hash << 1) + GEAR[byte]`, and a cut point is declared once
+/// `hash & mask == 0`. The mask starts stricter (more 1-bits, so a cut is
+/// less likely) while the current chunk is still below `avg_size`, then
+/// switches to a looser mask (fewer 1-bits) once past it, which pulls chunk
+/// sizes toward the average instead of letting them drift arbitrarily.
+pub fn chunk_data(data: &[u8], params: &ChunkParams) -> Vec<ChunkInfo> {
+    let avg_bits = (params.avg_size as f64).log2().round() as u32;
+    let mask_s = mask_with_ones(avg_bits + 1);
+    let mask_l = mask_with_ones(avg_bits.saturating_sub(1));
+
+    let mut chunks = Vec::new();
+    let mut offset = 0usize;
+    let len = data.len();
+
+    while offset < len {
+        let remaining = len - offset;
+        if remaining <= params.min_size {
+            chunks.push(make_chunk(data, offset, remaining));
+            break;
+        }
+
+        let max_len = remaining.min(params.max_size);
+        let mut hash: u64 = 0;
+        let mut cut = max_len;
+        let mut i = params.min_size;
+
+        while i < max_len {
+            let byte = data[offset + i];
+            hash = (hash << 1).wrapping_add(GEAR[byte as usize]);
+            let mask = if i < params.avg_size { mask_s } else { mask_l };
+            if hash & mask == 0 {
+                cut = i;
+                break;
+            }
+            i += 1;
+        }
+
+        chunks.push(make_chunk(data, offset, cut));
+        offset += cut;
+    }
+
+    chunks
+}
+
+/// Memory-maps `file_path` and chunks it in place, avoiding a full-file copy
+/// for the multi-hundred-MB files this is meant for.
+pub fn chunk_file(file_path: &Path, params: &ChunkParams) -> io::Result<Vec<ChunkInfo>> {
+    let file = File::open(file_path)?;
+    let mmap = unsafe { Mmap::map(&file)? };
+    Ok(chunk_data(&mmap, params))
+}
+
+/// Which chunk hashes appear in `new_chunks` but not `old_chunks` (added) and
+/// vice versa (removed) — the set of regions that actually changed between
+/// two versions of a file.
+#[derive(Debug, Clone, Default)]
+pub struct ChunkDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+pub fn diff(old_chunks: &[ChunkInfo], new_chunks: &[ChunkInfo]) -> ChunkDiff {
+    let old_hashes: HashSet<&str> = old_chunks.iter().map(|c| c.blake3.as_str()).collect();
+    let new_hashes: HashSet<&str> = new_chunks.iter().map(|c| c.blake3.as_str()).collect();
+
+    ChunkDiff {
+        added: new_hashes.difference(&old_hashes).map(|s| s.to_string()).collect(),
+        removed: old_hashes.difference(&new_hashes).map(|s| s.to_string()).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic filler bytes for test input — reuses the same
+    /// splitmix64 generator as the gear table so tests don't need a `rand`
+    /// dependency just to produce non-repeating content.
+    fn pseudo_random_bytes(seed: u64, len: usize) -> Vec<u8> {
+        let mut state = seed;
+        let mut out = Vec::with_capacity(len);
+        while out.len() < len {
+            state = splitmix64(state);
+            out.extend_from_slice(&state.to_le_bytes());
+        }
+        out.truncate(len);
+        out
+    }
+
+    #[test]
+    fn chunk_data_round_trips_to_input_length() {
+        let data = pseudo_random_bytes(1, 200_000);
+        let chunks = chunk_data(&data, &ChunkParams::default());
+
+        let total_length: u64 = chunks.iter().map(|c| c.length).sum();
+        assert_eq!(total_length, data.len() as u64);
+
+        let mut expected_offset = 0u64;
+        for chunk in &chunks {
+            assert_eq!(chunk.offset, expected_offset);
+            expected_offset += chunk.length;
+        }
+    }
+
+    #[test]
+    fn chunk_data_respects_min_and_max_bounds() {
+        let params = ChunkParams::default();
+        let data = pseudo_random_bytes(2, 500_000);
+        let chunks = chunk_data(&data, &params);
+
+        for (i, chunk) in chunks.iter().enumerate() {
+            assert!(chunk.length as usize <= params.max_size);
+            let is_last = i == chunks.len() - 1;
+            if !is_last {
+                assert!(chunk.length as usize >= params.min_size);
+            }
+        }
+    }
+
+    #[test]
+    fn chunk_data_empty_input_produces_no_chunks() {
+        assert!(chunk_data(&[], &ChunkParams::default()).is_empty());
+    }
+
+    #[test]
+    fn chunk_data_below_min_size_is_a_single_chunk() {
+        let params = ChunkParams::default();
+        let data = pseudo_random_bytes(3, params.min_size / 2);
+        let chunks = chunk_data(&data, &params);
+
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].offset, 0);
+        assert_eq!(chunks[0].length, data.len() as u64);
+    }
+
+    #[test]
+    fn diff_reports_nothing_for_identical_chunk_sets() {
+        let data = pseudo_random_bytes(4, 100_000);
+        let chunks = chunk_data(&data, &ChunkParams::default());
+
+        let delta = diff(&chunks, &chunks);
+        assert!(delta.added.is_empty());
+        assert!(delta.removed.is_empty());
+    }
+
+    #[test]
+    fn diff_reports_fully_disjoint_chunk_sets() {
+        let old_chunks = chunk_data(&pseudo_random_bytes(5, 50_000), &ChunkParams::default());
+        let new_chunks = chunk_data(&pseudo_random_bytes(6, 50_000), &ChunkParams::default());
+
+        let delta = diff(&old_chunks, &new_chunks);
+        assert_eq!(delta.added.len(), new_chunks.len());
+        assert_eq!(delta.removed.len(), old_chunks.len());
+    }
+}
@@ -2,9 +2,78 @@ use sha2::{Sha256, Digest};
 use md5::Md5;
 use std::fs::File;
 use std::io::{self, Read};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use std::path::{Path, PathBuf};
 use std::env;
+use memmap2::Mmap;
+
+mod chunking;
+use chunking::ChunkParams;
+
+/// Size of the in-memory buffer used for the algorithm matrix below.
+const BENCH_BUFFER_SIZE: usize = 64 * 1024 * 1024;
+/// How long to keep re-hashing the buffer for each algorithm.
+const BENCH_DURATION: Duration = Duration::from_secs(1);
+
+type HashFn = fn(&[u8]);
+
+fn hash_sha256_buf(buf: &[u8]) {
+    let mut hasher = Sha256::new();
+    hasher.update(buf);
+    let _ = hasher.finalize();
+}
+
+fn hash_md5_buf(buf: &[u8]) {
+    let mut hasher = Md5::new();
+    hasher.update(buf);
+    let _ = hasher.finalize();
+}
+
+fn hash_blake3_buf(buf: &[u8]) {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(buf);
+    let _ = hasher.finalize();
+}
+
+struct AlgoStats {
+    name: &'static str,
+    samples: usize,
+    mean_mb_s: f64,
+    stddev_mb_s: f64,
+}
+
+/// Repeatedly hashes `buffer` with `hash_fn` for `target_duration`, recording
+/// the throughput of each pass, then returns the mean and standard deviation
+/// across passes. Mean-only figures hide how noisy a candidate is; stddev
+/// matters once two algorithms are within a few percent of each other.
+fn benchmark_algorithm(
+    name: &'static str,
+    hash_fn: HashFn,
+    buffer: &[u8],
+    target_duration: Duration,
+) -> AlgoStats {
+    let mut throughputs_mb_s = Vec::new();
+    let deadline = Instant::now() + target_duration;
+
+    while Instant::now() < deadline {
+        let start = Instant::now();
+        hash_fn(buffer);
+        let elapsed = start.elapsed();
+        let mb_s = (buffer.len() as f64 / (1024.0 * 1024.0)) / elapsed.as_secs_f64();
+        throughputs_mb_s.push(mb_s);
+    }
+
+    let samples = throughputs_mb_s.len();
+    let mean_mb_s = throughputs_mb_s.iter().sum::<f64>() / samples as f64;
+    let variance = throughputs_mb_s
+        .iter()
+        .map(|v| (v - mean_mb_s).powi(2))
+        .sum::<f64>()
+        / samples as f64;
+    let stddev_mb_s = variance.sqrt();
+
+    AlgoStats { name, samples, mean_mb_s, stddev_mb_s }
+}
 
 fn compute_hash_sha256(file_path: &Path) -> io::Result<(String, u64, u128)> {
     let start = Instant::now();
@@ -30,6 +99,184 @@ fn compute_hash_sha256(file_path: &Path) -> io::Result<(String, u64, u128)> {
     Ok((hash_hex, total_bytes, elapsed_micros))
 }
 
+fn hex_decode(s: &str) -> Option<Vec<u8>> {
+    let bytes = s.as_bytes();
+    if !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+
+    fn nibble(b: u8) -> Option<u8> {
+        match b {
+            b'0'..=b'9' => Some(b - b'0'),
+            b'a'..=b'f' => Some(b - b'a' + 10),
+            b'A'..=b'F' => Some(b - b'A' + 10),
+            _ => None,
+        }
+    }
+
+    bytes
+        .chunks(2)
+        .map(|pair| Some((nibble(pair[0])? << 4) | nibble(pair[1])?))
+        .collect()
+}
+
+/// Byte-for-byte comparison that takes the same amount of time regardless of
+/// where (or whether) `a` and `b` first differ. A plain `==` can short-circuit
+/// on the first mismatching byte, which leaks how many leading bytes of a
+/// hash matched; folding every byte through `|=` on a volatile accumulator
+/// keeps the loop from being optimized into an early-exit comparison.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let mut diff: u8 = 0;
+    for i in 0..a.len() {
+        let av = unsafe { std::ptr::read_volatile(&a[i]) };
+        let bv = unsafe { std::ptr::read_volatile(&b[i]) };
+        let mut acc = unsafe { std::ptr::read_volatile(&diff) };
+        acc |= av ^ bv;
+        unsafe { std::ptr::write_volatile(&mut diff, acc) };
+    }
+
+    diff == 0
+}
+
+/// One `<hex-digest>  <path>` entry from a manifest file, mirroring the
+/// `sha256sum` output format so existing manifests can be reused as-is.
+struct ManifestEntry {
+    expected_hash: String,
+    file_path: PathBuf,
+}
+
+fn read_manifest(manifest_path: &Path) -> io::Result<Vec<ManifestEntry>> {
+    let content = std::fs::read_to_string(manifest_path)?;
+    let mut entries = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some((hash, file_path)) = line.split_once("  ").or_else(|| line.split_once(' ')) {
+            entries.push(ManifestEntry {
+                expected_hash: hash.trim().to_lowercase(),
+                file_path: PathBuf::from(file_path.trim()),
+            });
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Recomputes each manifest entry's SHA256 digest and reports match/mismatch,
+/// for detecting whether `Core.dll` or `level0` changed after a game update.
+/// Returns a process exit code: 0 if every entry matched, 1 otherwise, so
+/// this can gate automation.
+fn run_verify(manifest_path: &Path) -> i32 {
+    let entries = match read_manifest(manifest_path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("Failed to read manifest {}: {}", manifest_path.display(), e);
+            return 1;
+        }
+    };
+
+    let mut exit_code = 0;
+
+    for entry in entries {
+        match compute_hash_sha256(&entry.file_path) {
+            Ok((actual_hash, _, _)) => {
+                let expected_bytes = match hex_decode(&entry.expected_hash) {
+                    Some(bytes) => bytes,
+                    None => {
+                        eprintln!("  INVALID  {} (manifest hash is not valid hex)", entry.file_path.display());
+                        exit_code = 1;
+                        continue;
+                    }
+                };
+                let actual_bytes = hex_decode(&actual_hash).expect("our own SHA256 hex output is always valid");
+
+                if constant_time_eq(&expected_bytes, &actual_bytes) {
+                    println!("  OK       {}", entry.file_path.display());
+                } else {
+                    println!("  MISMATCH {}", entry.file_path.display());
+                    exit_code = 1;
+                }
+            }
+            Err(e) => {
+                eprintln!("  ERROR    {}: {}", entry.file_path.display(), e);
+                exit_code = 1;
+            }
+        }
+    }
+
+    exit_code
+}
+
+/// Splits `file_path` into FastCDC chunks and prints each one's offset,
+/// length, and digests.
+fn run_chunk(file_path: &Path) -> i32 {
+    let chunks = match chunking::chunk_file(file_path, &ChunkParams::default()) {
+        Ok(chunks) => chunks,
+        Err(e) => {
+            eprintln!("Failed to chunk {}: {}", file_path.display(), e);
+            return 1;
+        }
+    };
+
+    println!("=== Chunking {} ({} chunks) ===\n", file_path.display(), chunks.len());
+    for chunk in &chunks {
+        println!(
+            "  offset={:<10} length={:<8} sha256={} blake3={}",
+            chunk.offset, chunk.length, chunk.sha256, chunk.blake3
+        );
+    }
+
+    0
+}
+
+/// Chunks both `old_path` and `new_path` and reports which chunk hashes were
+/// added or removed between the two versions.
+fn run_chunk_diff(old_path: &Path, new_path: &Path) -> i32 {
+    let params = ChunkParams::default();
+
+    let old_chunks = match chunking::chunk_file(old_path, &params) {
+        Ok(chunks) => chunks,
+        Err(e) => {
+            eprintln!("Failed to chunk {}: {}", old_path.display(), e);
+            return 1;
+        }
+    };
+    let new_chunks = match chunking::chunk_file(new_path, &params) {
+        Ok(chunks) => chunks,
+        Err(e) => {
+            eprintln!("Failed to chunk {}: {}", new_path.display(), e);
+            return 1;
+        }
+    };
+
+    let delta = chunking::diff(&old_chunks, &new_chunks);
+
+    println!(
+        "=== Diffing {} chunks ({}) vs {} chunks ({}) ===\n",
+        old_chunks.len(),
+        old_path.display(),
+        new_chunks.len(),
+        new_path.display()
+    );
+    println!("  Added chunks:   {}", delta.added.len());
+    for hash in &delta.added {
+        println!("    + {}", hash);
+    }
+    println!("  Removed chunks: {}", delta.removed.len());
+    for hash in &delta.removed {
+        println!("    - {}", hash);
+    }
+
+    0
+}
+
 fn compute_hash_md5(file_path: &Path) -> io::Result<(String, u64, u128)> {
     let start = Instant::now();
 
@@ -54,6 +301,57 @@ fn compute_hash_md5(file_path: &Path) -> io::Result<(String, u64, u128)> {
     Ok((hash_hex, total_bytes, elapsed_micros))
 }
 
+fn compute_hash_blake3(file_path: &Path) -> io::Result<(String, u64, u128)> {
+    let start = Instant::now();
+
+    let mut file = File::open(file_path)?;
+    let mut hasher = blake3::Hasher::new();
+    let mut buffer = [0u8; 8192];
+    let mut total_bytes = 0u64;
+
+    loop {
+        let bytes_read = file.read(&mut buffer)?;
+        if bytes_read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..bytes_read]);
+        total_bytes += bytes_read as u64;
+    }
+
+    let hash = hasher.finalize();
+    let hash_hex = hash.to_hex().to_string();
+    let elapsed_micros = start.elapsed().as_micros();
+
+    Ok((hash_hex, total_bytes, elapsed_micros))
+}
+
+/// Memory-maps `file_path` and hashes it with BLAKE3's rayon-backed tree
+/// hashing (`update_rayon`), which walks the file's chunk tree across a
+/// thread pool instead of compressing chunks one at a time. Returns the
+/// digest alongside both the single-threaded and multi-threaded elapsed
+/// time so the two strategies can be compared directly on the same bytes.
+fn compute_hash_blake3_mmap(file_path: &Path) -> io::Result<(String, u64, u128, u128)> {
+    let file = File::open(file_path)?;
+    let total_bytes = file.metadata()?.len();
+    let mmap = unsafe { Mmap::map(&file)? };
+
+    let start = Instant::now();
+    let mut hasher_st = blake3::Hasher::new();
+    hasher_st.update(&mmap);
+    let _ = hasher_st.finalize();
+    let single_threaded_micros = start.elapsed().as_micros();
+
+    let start = Instant::now();
+    let mut hasher_mt = blake3::Hasher::new();
+    hasher_mt.update_rayon(&mmap);
+    let hash = hasher_mt.finalize();
+    let multi_threaded_micros = start.elapsed().as_micros();
+
+    let hash_hex = hash.to_hex().to_string();
+
+    Ok((hash_hex, total_bytes, single_threaded_micros, multi_threaded_micros))
+}
+
 fn get_default_paths() -> (PathBuf, PathBuf) {
     let home = env::var("HOME").expect("HOME not set");
     let game_path = PathBuf::from(home)
@@ -76,7 +374,41 @@ fn format_size(bytes: u64) -> String {
 }
 
 fn main() {
-    println!("=== Rust Hash Performance Test: SHA256 vs MD5 ===\n");
+    if env::args().nth(1).as_deref() == Some("verify") {
+        let manifest_path = match env::args().nth(2) {
+            Some(p) => PathBuf::from(p),
+            None => {
+                eprintln!("Usage: rust_hash_test verify <manifest-file>");
+                std::process::exit(1);
+            }
+        };
+        println!("=== Verifying against manifest: {} ===\n", manifest_path.display());
+        std::process::exit(run_verify(&manifest_path));
+    }
+
+    if env::args().nth(1).as_deref() == Some("chunk") {
+        let file_path = match env::args().nth(2) {
+            Some(p) => PathBuf::from(p),
+            None => {
+                eprintln!("Usage: rust_hash_test chunk <file>");
+                std::process::exit(1);
+            }
+        };
+        std::process::exit(run_chunk(&file_path));
+    }
+
+    if env::args().nth(1).as_deref() == Some("chunk-diff") {
+        let (old_path, new_path) = match (env::args().nth(2), env::args().nth(3)) {
+            (Some(old), Some(new)) => (PathBuf::from(old), PathBuf::from(new)),
+            _ => {
+                eprintln!("Usage: rust_hash_test chunk-diff <old-file> <new-file>");
+                std::process::exit(1);
+            }
+        };
+        std::process::exit(run_chunk_diff(&old_path, &new_path));
+    }
+
+    println!("=== Rust Hash Performance Test: SHA256 vs MD5 vs BLAKE3 ===\n");
 
     let (core_dll_path, level0_path) = if env::args().len() >= 3 {
         let mut args = env::args().skip(1);
@@ -115,6 +447,31 @@ fn main() {
         }
     }
 
+    // BLAKE3
+    match compute_hash_blake3(&core_dll_path) {
+        Ok((hash, _, micros)) => {
+            println!("  BLAKE3 Hash: {}", hash);
+            println!("  Time:        {:.3} ms ({} µs)", micros as f64 / 1000.0, micros);
+        }
+        Err(e) => {
+            eprintln!("  BLAKE3 Error: {}", e);
+        }
+    }
+
+    // BLAKE3, memory-mapped, single- vs multi-threaded
+    match compute_hash_blake3_mmap(&core_dll_path) {
+        Ok((hash, size, st_micros, mt_micros)) => {
+            let st_mb_s = (size as f64 / (1024.0 * 1024.0)) / (st_micros as f64 / 1_000_000.0);
+            let mt_mb_s = (size as f64 / (1024.0 * 1024.0)) / (mt_micros as f64 / 1_000_000.0);
+            println!("  BLAKE3 (mmap) Hash: {}", hash);
+            println!("  Single-threaded:    {:.3} ms ({:.1} MB/s)", st_micros as f64 / 1000.0, st_mb_s);
+            println!("  Multi-threaded:     {:.3} ms ({:.1} MB/s)", mt_micros as f64 / 1000.0, mt_mb_s);
+        }
+        Err(e) => {
+            eprintln!("  BLAKE3 (mmap) Error: {}", e);
+        }
+    }
+
     println!();
 
     // Test level0
@@ -143,32 +500,98 @@ fn main() {
         }
     }
 
+    // BLAKE3
+    match compute_hash_blake3(&level0_path) {
+        Ok((hash, _, micros)) => {
+            println!("  BLAKE3 Hash: {}", hash);
+            println!("  Time:        {:.3} ms ({} µs)", micros as f64 / 1000.0, micros);
+        }
+        Err(e) => {
+            eprintln!("  BLAKE3 Error: {}", e);
+        }
+    }
+
+    // BLAKE3, memory-mapped, single- vs multi-threaded (level0 is hundreds of MB,
+    // so this is where the parallel tree hashing actually pays off)
+    match compute_hash_blake3_mmap(&level0_path) {
+        Ok((hash, size, st_micros, mt_micros)) => {
+            let st_mb_s = (size as f64 / (1024.0 * 1024.0)) / (st_micros as f64 / 1_000_000.0);
+            let mt_mb_s = (size as f64 / (1024.0 * 1024.0)) / (mt_micros as f64 / 1_000_000.0);
+            println!("  BLAKE3 (mmap) Hash: {}", hash);
+            println!("  Single-threaded:    {:.3} ms ({:.1} MB/s)", st_micros as f64 / 1000.0, st_mb_s);
+            println!("  Multi-threaded:     {:.3} ms ({:.1} MB/s)", mt_micros as f64 / 1000.0, mt_mb_s);
+        }
+        Err(e) => {
+            eprintln!("  BLAKE3 (mmap) Error: {}", e);
+        }
+    }
+
     println!();
 
-    // Combined performance test (10 iterations)
-    println!("Performance comparison (10 iterations each):");
+    // Algorithm matrix: hash a fixed-size in-memory buffer repeatedly for a
+    // target duration per algorithm and report throughput, not raw elapsed
+    // time, since that's what actually transfers between a small Core.dll
+    // delta and a multi-hundred-MB level0 file.
+    println!("Algorithm matrix ({} buffer, {:?} per algorithm):", format_size(BENCH_BUFFER_SIZE as u64), BENCH_DURATION);
 
-    let mut sha256_times = Vec::new();
-    let mut md5_times = Vec::new();
+    let buffer = vec![0xa5u8; BENCH_BUFFER_SIZE];
+    let algorithms: &[(&str, HashFn)] = &[
+        ("sha256", hash_sha256_buf),
+        ("md5", hash_md5_buf),
+        ("blake3", hash_blake3_buf),
+    ];
 
-    for _ in 0..10 {
-        // SHA256
-        let start = Instant::now();
-        let _ = compute_hash_sha256(&core_dll_path);
-        let _ = compute_hash_sha256(&level0_path);
-        sha256_times.push(start.elapsed().as_micros());
+    for (name, hash_fn) in algorithms {
+        let stats = benchmark_algorithm(name, *hash_fn, &buffer, BENCH_DURATION);
+        println!(
+            "  {:8}: {:8.1} MB/s  (± {:.1} MB/s, n={})",
+            stats.name, stats.mean_mb_s, stats.stddev_mb_s, stats.samples
+        );
+    }
+}
 
-        // MD5
-        let start = Instant::now();
-        let _ = compute_hash_md5(&core_dll_path);
-        let _ = compute_hash_md5(&level0_path);
-        md5_times.push(start.elapsed().as_micros());
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_decode_accepts_valid_hex() {
+        assert_eq!(hex_decode("00ff"), Some(vec![0x00, 0xff]));
+        assert_eq!(hex_decode("DEADBEEF"), Some(vec![0xde, 0xad, 0xbe, 0xef]));
+        assert_eq!(hex_decode(""), Some(vec![]));
+    }
+
+    #[test]
+    fn hex_decode_rejects_odd_length() {
+        assert_eq!(hex_decode("abc"), None);
     }
 
-    let sha256_avg = sha256_times.iter().sum::<u128>() / sha256_times.len() as u128;
-    let md5_avg = md5_times.iter().sum::<u128>() / md5_times.len() as u128;
+    #[test]
+    fn hex_decode_rejects_non_hex_ascii() {
+        assert_eq!(hex_decode("zz"), None);
+    }
 
-    println!("  SHA256 avg: {:.3} ms", sha256_avg as f64 / 1000.0);
-    println!("  MD5 avg:    {:.3} ms", md5_avg as f64 / 1000.0);
-    println!("  Speedup:    {:.2}x faster with MD5", sha256_avg as f64 / md5_avg as f64);
+    #[test]
+    fn hex_decode_does_not_panic_on_non_ascii_even_byte_length() {
+        // "a" plus a 3-byte UTF-8 character is 4 bytes total (even), which
+        // used to panic by slicing the `str` on a non-char-boundary index.
+        assert_eq!(hex_decode("a€"), None);
+    }
+
+    #[test]
+    fn constant_time_eq_matches_equal_slices() {
+        assert!(constant_time_eq(&[1, 2, 3], &[1, 2, 3]));
+        assert!(constant_time_eq(&[], &[]));
+    }
+
+    #[test]
+    fn constant_time_eq_rejects_different_length() {
+        assert!(!constant_time_eq(&[1, 2, 3], &[1, 2]));
+    }
+
+    #[test]
+    fn constant_time_eq_rejects_mismatched_bytes() {
+        assert!(!constant_time_eq(&[1, 2, 3], &[1, 2, 4]));
+        assert!(!constant_time_eq(&[0, 0, 0], &[0, 0, 1]));
+    }
 }
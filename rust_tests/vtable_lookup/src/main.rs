@@ -1,7 +1,11 @@
 use std::fs::File;
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{self, Read, Seek, SeekFrom};
 use std::time::Instant;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::env;
+use libc::{c_void, iovec};
+
+mod scan;
 
 fn find_hackmud_pid() -> Option<u32> {
     // Try reading from scanner.pid file first
@@ -62,6 +66,68 @@ fn benchmark_memory_reads(pid: u32, addresses: &[(usize, usize)]) -> (usize, u12
     (successful_reads, elapsed_micros)
 }
 
+/// Reads every `(addr, size)` target from `pid`'s address space in a single
+/// `process_vm_readv(2)` call instead of one `seek`+`read_exact` syscall pair
+/// per address. This is the standard fast path for bulk memory scanning,
+/// since the per-read overhead (not the actual copy) is what dominates when
+/// reading many small, scattered locations.
+fn read_memory_batch(pid: u32, addresses: &[(usize, usize)]) -> io::Result<(usize, Vec<Vec<u8>>)> {
+    let mut buffers: Vec<Vec<u8>> = addresses.iter().map(|&(_, size)| vec![0u8; size]).collect();
+
+    let mut local_iov: Vec<iovec> = buffers
+        .iter_mut()
+        .map(|buf| iovec {
+            iov_base: buf.as_mut_ptr() as *mut c_void,
+            iov_len: buf.len(),
+        })
+        .collect();
+
+    let remote_iov: Vec<iovec> = addresses
+        .iter()
+        .map(|&(addr, size)| iovec {
+            iov_base: addr as *mut c_void,
+            iov_len: size,
+        })
+        .collect();
+
+    let bytes_read = unsafe {
+        libc::process_vm_readv(
+            pid as libc::pid_t,
+            local_iov.as_mut_ptr(),
+            local_iov.len() as u64,
+            remote_iov.as_ptr(),
+            remote_iov.len() as u64,
+            0,
+        )
+    };
+
+    if bytes_read < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok((bytes_read as usize, buffers))
+}
+
+/// Same workload as `benchmark_memory_reads`, but gathers all addresses into
+/// one `process_vm_readv` call per iteration instead of reading them one at a
+/// time through `/proc/pid/mem`.
+fn benchmark_memory_reads_vm_readv(pid: u32, addresses: &[(usize, usize)]) -> (usize, u128) {
+    let start = Instant::now();
+
+    let successful_reads = match read_memory_batch(pid, addresses) {
+        // process_vm_readv stops at the first iovec it can't fully satisfy,
+        // so a full byte count means every requested address was read.
+        Ok((bytes_read, _)) => {
+            let total_requested: usize = addresses.iter().map(|&(_, size)| size).sum();
+            if bytes_read == total_requested { addresses.len() } else { 0 }
+        }
+        Err(_) => 0,
+    };
+
+    let elapsed_micros = start.elapsed().as_micros();
+    (successful_reads, elapsed_micros)
+}
+
 fn get_heap_addresses(pid: u32) -> Vec<(usize, usize)> {
     let maps_path = format!("/proc/{}/maps", pid);
     let maps_content = match std::fs::read_to_string(&maps_path) {
@@ -98,7 +164,75 @@ fn get_heap_addresses(pid: u32) -> Vec<(usize, usize)> {
     addresses
 }
 
+/// Scans `pid`'s readable memory, bins it, hashes each bin in parallel, and
+/// prints per-bin digests plus the cache-hit/recompute counts.
+fn run_scan(pid: u32, bin_count: usize, cache_path: &Path) {
+    println!(
+        "Scanning readable regions of PID {} into {} bins (cache: {})...\n",
+        pid,
+        bin_count,
+        cache_path.display()
+    );
+
+    match scan::scan_and_fingerprint(pid, bin_count, cache_path) {
+        Ok(report) => {
+            for bin in &report.bins {
+                let status = if bin.cache_hit { "cache hit" } else { "recomputed" };
+                let skip_note = if bin.skipped_regions > 0 {
+                    format!(", {} region(s) skipped (unreadable)", bin.skipped_regions)
+                } else {
+                    String::new()
+                };
+                println!(
+                    "  bin [{:#x}-{:#x}]: {} ({}{})",
+                    bin.range.0,
+                    bin.range.1,
+                    scan::hex_digest(&bin.digest),
+                    status,
+                    skip_note
+                );
+            }
+            println!("\nRoot digest: {}", report.root_digest);
+            println!("Cache hits:  {}/{}", report.cache_hits, report.bins.len());
+            println!("Recomputed:  {}/{}", report.cache_misses, report.bins.len());
+            if report.cache_hits > 0 {
+                println!(
+                    "NOTE: a cache hit means this bin's address range AND a cheap {}-byte-per-region \
+                     content sample both matched the last scan — it is a heuristic, not a guarantee \
+                     that every byte in the bin is unchanged.",
+                    scan::SAMPLE_BYTES
+                );
+            }
+        }
+        Err(e) => {
+            eprintln!("Scan failed: {}", e);
+        }
+    }
+}
+
 fn main() {
+    if env::args().nth(1).as_deref() == Some("scan") {
+        let pid = match find_hackmud_pid() {
+            Some(p) => p,
+            None => {
+                eprintln!("Error: hackmud process not found");
+                eprintln!("Make sure hackmud is running");
+                std::process::exit(1);
+            }
+        };
+        let bin_count = env::args()
+            .nth(2)
+            .and_then(|s| s.parse::<usize>().ok())
+            .unwrap_or(16);
+        let cache_path = env::args()
+            .nth(3)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| std::env::temp_dir().join("hackmud_scan_cache.bin"));
+
+        run_scan(pid, bin_count, &cache_path);
+        return;
+    }
+
     println!("=== Rust Memory Reading Benchmark ===\n");
 
     // Find hackmud PID
@@ -125,17 +259,17 @@ fn main() {
 
     println!("Found {} addresses to test\n", addresses.len());
 
-    // Run benchmark
-    println!("Running benchmark (reading {} locations)...", addresses.len());
+    // Run benchmark: /proc/pid/mem, one seek+read_exact per address
+    println!("Running benchmark (reading {} locations via /proc/pid/mem)...", addresses.len());
     let (successful, elapsed_micros) = benchmark_memory_reads(pid, &addresses);
 
-    println!("\nResults:");
+    println!("\nResults (/proc/pid/mem):");
     println!("  Successful reads: {}/{}", successful, addresses.len());
     println!("  Total time:       {:.3} ms", elapsed_micros as f64 / 1000.0);
     println!("  Time per read:    {:.3} µs", elapsed_micros as f64 / addresses.len() as f64);
 
     // Run multiple iterations for average
-    println!("\n Running 10 iterations for average...");
+    println!("\n Running 10 iterations for average (/proc/pid/mem)...");
     let mut times = Vec::new();
 
     for _ in 0..10 {
@@ -151,4 +285,32 @@ fn main() {
     println!("  Min:     {:.3} ms", *min as f64 / 1000.0);
     println!("  Max:     {:.3} ms", *max as f64 / 1000.0);
     println!("  Per read: {:.3} µs", avg as f64 / addresses.len() as f64);
+
+    // Run the same workload through batched process_vm_readv
+    println!("\nRunning benchmark (reading {} locations via process_vm_readv)...", addresses.len());
+    let (successful_vm, elapsed_micros_vm) = benchmark_memory_reads_vm_readv(pid, &addresses);
+
+    println!("\nResults (process_vm_readv):");
+    println!("  Successful reads: {}/{}", successful_vm, addresses.len());
+    println!("  Total time:       {:.3} ms", elapsed_micros_vm as f64 / 1000.0);
+    println!("  Time per read:    {:.3} µs", elapsed_micros_vm as f64 / addresses.len() as f64);
+
+    println!("\n Running 10 iterations for average (process_vm_readv)...");
+    let mut times_vm = Vec::new();
+
+    for _ in 0..10 {
+        let (_, elapsed) = benchmark_memory_reads_vm_readv(pid, &addresses);
+        times_vm.push(elapsed);
+    }
+
+    let avg_vm = times_vm.iter().sum::<u128>() / times_vm.len() as u128;
+    let min_vm = times_vm.iter().min().unwrap();
+    let max_vm = times_vm.iter().max().unwrap();
+
+    println!("  Average: {:.3} ms", avg_vm as f64 / 1000.0);
+    println!("  Min:     {:.3} ms", *min_vm as f64 / 1000.0);
+    println!("  Max:     {:.3} ms", *max_vm as f64 / 1000.0);
+    println!("  Per read: {:.3} µs", avg_vm as f64 / addresses.len() as f64);
+
+    println!("\nSpeedup:    {:.2}x faster with process_vm_readv", avg as f64 / avg_vm as f64);
 }
@@ -0,0 +1,319 @@
+//! Scan-and-fingerprint subsystem for a running hackmud process.
+//!
+//! Maps every readable region out of `/proc/pid/maps`, splits the regions
+//! into address-range bins, and hashes each bin in parallel with rayon. Bin
+//! digests are cached on disk keyed by the bin's address range plus a cheap
+//! content sample, so repeated scans of a long-running process only re-hash
+//! the bins that actually changed.
+//!
+//! Caveat: a bin whose range AND content sample both match the cache is
+//! assumed unchanged without re-reading the whole bin. The sample only
+//! covers the first `SAMPLE_BYTES` of each region, so a write further into a
+//! large region can in principle go undetected between scans — this trades
+//! exhaustiveness for speed on the assumption that most in-place writes
+//! touch the start of a buffer or happen often enough to show up eventually.
+
+use memmap2::{Mmap, MmapMut};
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+pub const DIGEST_SIZE: usize = 32;
+/// How many leading bytes of each region get folded into the cheap "is this
+/// bin still the same content" sample digest.
+pub const SAMPLE_BYTES: usize = 256;
+const RECORD_SIZE: usize = 8 + 8 + DIGEST_SIZE + DIGEST_SIZE;
+
+/// Pseudo-mappings that show up as readable (`r--p`) in `/proc/pid/maps` but
+/// return `EIO` when actually read through `/proc/pid/mem` — they must be
+/// skipped rather than attempted.
+const UNREADABLE_PSEUDO_MAPPINGS: &[&str] = &["[vvar]", "[vvar_vclock]", "[vsyscall]"];
+
+pub fn hex_digest(digest: &[u8; DIGEST_SIZE]) -> String {
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// One bin's address range, combined digest, and whether it came from cache.
+pub struct BinResult {
+    pub range: (u64, u64),
+    pub digest: [u8; DIGEST_SIZE],
+    pub cache_hit: bool,
+    /// Regions within this bin that returned an I/O error when read (e.g. a
+    /// page that got swapped out or unmapped mid-scan) and were excluded from
+    /// the bin's digest instead of aborting the whole scan.
+    pub skipped_regions: usize,
+    sample_digest: [u8; DIGEST_SIZE],
+}
+
+pub struct ScanReport {
+    pub bins: Vec<BinResult>,
+    pub root_digest: String,
+    pub cache_hits: usize,
+    pub cache_misses: usize,
+}
+
+fn read_readable_regions(pid: u32) -> io::Result<Vec<(u64, u64)>> {
+    let maps_path = format!("/proc/{}/maps", pid);
+    let content = std::fs::read_to_string(maps_path)?;
+    let mut regions = Vec::new();
+
+    for line in content.lines() {
+        let mut parts = line.split_whitespace();
+        let addr_range = match parts.next() {
+            Some(s) => s,
+            None => continue,
+        };
+        let perms = match parts.next() {
+            Some(s) => s,
+            None => continue,
+        };
+        if !perms.starts_with('r') {
+            continue;
+        }
+
+        // offset, dev, inode
+        parts.next();
+        parts.next();
+        parts.next();
+        let pathname: String = parts.collect::<Vec<_>>().join(" ");
+        if UNREADABLE_PSEUDO_MAPPINGS.contains(&pathname.as_str()) {
+            continue;
+        }
+
+        if let Some((start_str, end_str)) = addr_range.split_once('-') {
+            if let (Ok(start), Ok(end)) = (
+                u64::from_str_radix(start_str, 16),
+                u64::from_str_radix(end_str, 16),
+            ) {
+                regions.push((start, end));
+            }
+        }
+    }
+
+    Ok(regions)
+}
+
+/// Splits `regions` into `bin_count` bins by address range (or one bin per
+/// region when there are fewer regions than that): regions are sorted by
+/// start address, then divided into contiguous groups whose region counts
+/// differ by at most one, so each bin covers a contiguous slice of the
+/// address space rather than an arbitrary scatter of regions.
+fn bin_regions(mut regions: Vec<(u64, u64)>, bin_count: usize) -> Vec<Vec<(u64, u64)>> {
+    if regions.is_empty() || bin_count == 0 {
+        return Vec::new();
+    }
+
+    regions.sort_by_key(|&(start, _)| start);
+    let bin_count = bin_count.min(regions.len());
+    let base = regions.len() / bin_count;
+    let extra = regions.len() % bin_count;
+
+    let mut bins = Vec::with_capacity(bin_count);
+    let mut rest = regions.as_slice();
+    for i in 0..bin_count {
+        let (head, tail) = rest.split_at(base + usize::from(i < extra));
+        bins.push(head.to_vec());
+        rest = tail;
+    }
+    bins
+}
+
+fn bin_range(bin: &[(u64, u64)]) -> (u64, u64) {
+    let start = bin.iter().map(|&(s, _)| s).min().unwrap_or(0);
+    let end = bin.iter().map(|&(_, e)| e).max().unwrap_or(0);
+    (start, end)
+}
+
+fn read_range(mem_file: &mut File, start: u64, len: usize) -> io::Result<Vec<u8>> {
+    mem_file.seek(SeekFrom::Start(start))?;
+    let mut buffer = vec![0u8; len];
+    mem_file.read_exact(&mut buffer)?;
+    Ok(buffer)
+}
+
+fn hash_region(mem_file: &mut File, start: u64, end: u64) -> io::Result<[u8; DIGEST_SIZE]> {
+    let buffer = read_range(mem_file, start, (end - start) as usize)?;
+    Ok(*blake3::hash(&buffer).as_bytes())
+}
+
+/// Hashes every region in `bin` and folds the per-region digests into a
+/// single digest for the bin. Opens its own `/proc/pid/mem` handle since this
+/// runs on a rayon worker thread alongside other bins.
+///
+/// A region that fails to read (for example a mapping that's readable
+/// according to `/proc/pid/maps` but returns `EIO`, such as a page that was
+/// swapped out or unmapped between listing and reading) is excluded from the
+/// bin's digest and counted as skipped, rather than aborting the whole scan.
+fn hash_bin(pid: u32, bin: &[(u64, u64)]) -> io::Result<([u8; DIGEST_SIZE], usize)> {
+    let mut mem_file = File::open(format!("/proc/{}/mem", pid))?;
+    let mut hasher = blake3::Hasher::new();
+    let mut skipped_regions = 0;
+
+    for &(start, end) in bin {
+        match hash_region(&mut mem_file, start, end) {
+            Ok(digest) => hasher.update(&digest),
+            Err(_) => {
+                skipped_regions += 1;
+                continue;
+            }
+        };
+    }
+
+    Ok((*hasher.finalize().as_bytes(), skipped_regions))
+}
+
+/// Hashes only the first `SAMPLE_BYTES` of each region in `bin` (or the whole
+/// region if it's smaller) as a cheap stand-in for "has this bin's content
+/// changed". Used to decide whether a range match against the cache is
+/// actually still trustworthy, since a process's heap content changes far
+/// more often than its VMA bounds do.
+fn sample_bin(pid: u32, bin: &[(u64, u64)]) -> io::Result<[u8; DIGEST_SIZE]> {
+    let mut mem_file = File::open(format!("/proc/{}/mem", pid))?;
+    let mut hasher = blake3::Hasher::new();
+
+    for &(start, end) in bin {
+        let len = ((end - start) as usize).min(SAMPLE_BYTES);
+        if len == 0 {
+            continue;
+        }
+        match read_range(&mut mem_file, start, len) {
+            Ok(buffer) => hasher.update(&buffer),
+            Err(_) => continue,
+        };
+    }
+
+    Ok(*hasher.finalize().as_bytes())
+}
+
+/// A cached bin: its full content digest plus the cheap sample digest that
+/// was true at the time it was stored, so a future scan can tell whether the
+/// bin's content (not just its bounds) is still the same.
+struct CacheEntry {
+    sample_digest: [u8; DIGEST_SIZE],
+    full_digest: [u8; DIGEST_SIZE],
+}
+
+/// Reads the on-disk cache: a header with an entry count followed by
+/// fixed-size `{range_start, range_end, sample_digest, full_digest}`
+/// records. The file is memory-mapped and every record is read into a map
+/// keyed by the record's range.
+fn load_cache(cache_path: &Path) -> io::Result<HashMap<(u64, u64), CacheEntry>> {
+    let mut entries = HashMap::new();
+
+    let file = match File::open(cache_path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(entries),
+        Err(e) => return Err(e),
+    };
+    if file.metadata()?.len() < 8 {
+        return Ok(entries);
+    }
+
+    let mmap = unsafe { Mmap::map(&file)? };
+    let entry_count = u64::from_le_bytes(mmap[0..8].try_into().unwrap()) as usize;
+    let mut offset = 8;
+
+    for _ in 0..entry_count {
+        if offset + RECORD_SIZE > mmap.len() {
+            break;
+        }
+        let range_start = u64::from_le_bytes(mmap[offset..offset + 8].try_into().unwrap());
+        let range_end = u64::from_le_bytes(mmap[offset + 8..offset + 16].try_into().unwrap());
+        let mut sample_digest = [0u8; DIGEST_SIZE];
+        sample_digest.copy_from_slice(&mmap[offset + 16..offset + 16 + DIGEST_SIZE]);
+        let mut full_digest = [0u8; DIGEST_SIZE];
+        full_digest.copy_from_slice(&mmap[offset + 16 + DIGEST_SIZE..offset + RECORD_SIZE]);
+        entries.insert((range_start, range_end), CacheEntry { sample_digest, full_digest });
+        offset += RECORD_SIZE;
+    }
+
+    Ok(entries)
+}
+
+fn save_cache(cache_path: &Path, entries: &[((u64, u64), CacheEntry)]) -> io::Result<()> {
+    let total_size = 8 + entries.len() * RECORD_SIZE;
+
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(cache_path)?;
+    file.set_len(total_size as u64)?;
+
+    let mut mmap = unsafe { MmapMut::map_mut(&file)? };
+    mmap[0..8].copy_from_slice(&(entries.len() as u64).to_le_bytes());
+
+    let mut offset = 8;
+    for ((start, end), entry) in entries {
+        mmap[offset..offset + 8].copy_from_slice(&start.to_le_bytes());
+        mmap[offset + 8..offset + 16].copy_from_slice(&end.to_le_bytes());
+        mmap[offset + 16..offset + 16 + DIGEST_SIZE].copy_from_slice(&entry.sample_digest);
+        mmap[offset + 16 + DIGEST_SIZE..offset + RECORD_SIZE].copy_from_slice(&entry.full_digest);
+        offset += RECORD_SIZE;
+    }
+
+    mmap.flush()
+}
+
+/// Scans `pid`'s readable memory regions, bins them into `bin_count` groups,
+/// and hashes each bin in parallel with rayon.
+///
+/// A bin only counts as a cache hit if BOTH its address range and its cheap
+/// content sample match a record in `cache_path` — matching bounds alone is
+/// not enough, since a live process's heap content changes far more often
+/// than its VMA bounds do. Everything else (new bins, bins with no cache
+/// record, or bins whose sample drifted) is fully recomputed, and the cache
+/// file is rewritten with the fresh set of digests.
+pub fn scan_and_fingerprint(pid: u32, bin_count: usize, cache_path: &Path) -> io::Result<ScanReport> {
+    let regions = read_readable_regions(pid)?;
+    let bins = bin_regions(regions, bin_count);
+    let cache = load_cache(cache_path)?;
+
+    let results: Vec<io::Result<BinResult>> = bins
+        .par_iter()
+        .map(|bin| {
+            let range = bin_range(bin);
+            let sample_digest = sample_bin(pid, bin)?;
+
+            if let Some(cached) = cache.get(&range) {
+                if cached.sample_digest == sample_digest {
+                    return Ok(BinResult {
+                        range,
+                        digest: cached.full_digest,
+                        cache_hit: true,
+                        skipped_regions: 0,
+                        sample_digest,
+                    });
+                }
+            }
+
+            let (digest, skipped_regions) = hash_bin(pid, bin)?;
+            Ok(BinResult { range, digest, cache_hit: false, skipped_regions, sample_digest })
+        })
+        .collect();
+
+    let mut bin_results = Vec::with_capacity(results.len());
+    for result in results {
+        bin_results.push(result?);
+    }
+
+    let cache_hits = bin_results.iter().filter(|b| b.cache_hit).count();
+    let cache_misses = bin_results.len() - cache_hits;
+
+    let mut root_hasher = blake3::Hasher::new();
+    for bin in &bin_results {
+        root_hasher.update(&bin.digest);
+    }
+    let root_digest = root_hasher.finalize().to_hex().to_string();
+
+    let entries: Vec<((u64, u64), CacheEntry)> = bin_results
+        .iter()
+        .map(|b| (b.range, CacheEntry { sample_digest: b.sample_digest, full_digest: b.digest }))
+        .collect();
+    save_cache(cache_path, &entries)?;
+
+    Ok(ScanReport { bins: bin_results, root_digest, cache_hits, cache_misses })
+}